@@ -0,0 +1,173 @@
+use std::io::{self, Write};
+
+use crate::{Object, Result};
+
+// Canonical git object modes, stored in trees as octal text and read back as
+// decimal. Locally written trees keep the full `st_mode` (e.g. a symlink is
+// `120777`), so compare against the normalized mode rather than these directly.
+const MODE_EXEC: u32 = 100755;
+const MODE_SYMLINK: u32 = 120000;
+
+/// Collapse a stored entry mode to git's canonical form. Trees written by this
+/// tool record the raw `st_mode`, while trees from real git already carry the
+/// canonical value; both map to `120000`, `100755`, or `100644`.
+fn normalize_mode(mode: u32) -> u32 {
+    match mode / 1000 {
+        120 => MODE_SYMLINK,
+        100 if (mode % 1000 / 100) % 2 == 1 => MODE_EXEC,
+        _ => 100644,
+    }
+}
+
+/// Stream a ustar archive of `tree_ish`'s contents to stdout, one entry per
+/// blob. `prefix` is prepended to every entry name and `strip_components`
+/// drops that many leading path components (selecting a subtree).
+pub fn archive(tree_ish: &str, prefix: &str, strip_components: usize) -> Result<()> {
+    let root = resolve_tree(tree_ish)?;
+    let mut out: Vec<u8> = Vec::new();
+    write_tree(&root, "", prefix, strip_components, &mut out)?;
+    // A ustar archive ends with two zero-filled blocks.
+    out.resize(out.len() + 1024, 0);
+    io::stdout().write_all(&out)?;
+    Ok(())
+}
+
+/// Resolve a tree-ish to a tree object's sha, dereferencing a commit if given.
+fn resolve_tree(tree_ish: &str) -> Result<String> {
+    match Object::read_from_sha1(tree_ish)? {
+        Object::Tree { .. } => Ok(tree_ish.to_string()),
+        Object::Commit { tree, .. } => Ok(tree),
+        _ => Err(Box::new(crate::GitError::InvalidArgs(
+            "not a tree-ish".to_string(),
+        ))),
+    }
+}
+
+fn write_tree(
+    sha: &str,
+    rel: &str,
+    prefix: &str,
+    strip: usize,
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    if let Object::Tree { entries, .. } = Object::read_from_sha1(sha)? {
+        for entry in entries {
+            let path = if rel.is_empty() {
+                entry.name.clone()
+            } else {
+                format!("{}/{}", rel, entry.name)
+            };
+            if entry.type_ == "tree" {
+                write_tree(&entry.sha1, &path, prefix, strip, out)?;
+            } else if let Object::Blob { content, .. } = Object::read_from_sha1(&entry.sha1)? {
+                let name = match strip_components(&path, strip) {
+                    Some(name) => format!("{}{}", prefix, name),
+                    None => continue,
+                };
+                let mode = normalize_mode(entry.mode);
+                if mode == MODE_SYMLINK {
+                    // Emit a symlink entry pointing at the stored target,
+                    // rather than expanding the target's bytes.
+                    let target = String::from_utf8_lossy(&content);
+                    out.extend_from_slice(&header(&name, 0, b'2', &target, 0o777));
+                } else {
+                    let perms = if mode == MODE_EXEC { 0o755 } else { 0o644 };
+                    out.extend_from_slice(&header(&name, content.len(), b'0', "", perms));
+                    out.extend_from_slice(&content);
+                    // Pad the file data up to a 512-byte block boundary.
+                    let pad = (512 - content.len() % 512) % 512;
+                    out.resize(out.len() + pad, 0);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Drop the first `n` path components, returning `None` when nothing would be
+/// left (the path has `n` or fewer components).
+fn strip_components(path: &str, n: usize) -> Option<String> {
+    if n == 0 {
+        return Some(path.to_string());
+    }
+    let components: Vec<&str> = path.split('/').collect();
+    if components.len() <= n {
+        None
+    } else {
+        Some(components[n..].join("/"))
+    }
+}
+
+/// Build a 512-byte ustar header block.
+fn header(name: &str, size: usize, typeflag: u8, linkname: &str, mode: u32) -> [u8; 512] {
+    let mut h = [0u8; 512];
+    put(&mut h, 0, name.as_bytes(), 100);
+    put_octal(&mut h, 100, mode as u64, 8);
+    put_octal(&mut h, 108, 0, 8); // uid
+    put_octal(&mut h, 116, 0, 8); // gid
+    put_octal(&mut h, 124, size as u64, 12);
+    put_octal(&mut h, 136, 0, 12); // mtime
+    // The checksum field is treated as spaces while the sum is computed.
+    for b in &mut h[148..156] {
+        *b = b' ';
+    }
+    h[156] = typeflag;
+    put(&mut h, 157, linkname.as_bytes(), 100);
+    put(&mut h, 257, b"ustar\0", 6);
+    h[263] = b'0';
+    h[264] = b'0';
+
+    let sum: u32 = h.iter().map(|&b| b as u32).sum();
+    let chksum = format!("{:06o}\0 ", sum);
+    h[148..156].copy_from_slice(chksum.as_bytes());
+    h
+}
+
+fn put(h: &mut [u8; 512], at: usize, bytes: &[u8], max: usize) {
+    let n = bytes.len().min(max);
+    h[at..at + n].copy_from_slice(&bytes[..n]);
+}
+
+fn put_octal(h: &mut [u8; 512], at: usize, value: u64, field: usize) {
+    // `field - 1` octal digits, zero-padded, followed by a trailing NUL.
+    let s = format!("{:0>width$o}\0", value, width = field - 1);
+    h[at..at + field].copy_from_slice(s.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Recompute a ustar header checksum treating the checksum field as spaces,
+    /// matching how `tar` verifies an entry.
+    fn computed_checksum(h: &[u8; 512]) -> u32 {
+        h.iter()
+            .enumerate()
+            .map(|(i, &b)| if (148..156).contains(&i) { b' ' as u32 } else { b as u32 })
+            .sum()
+    }
+
+    #[test]
+    fn header_checksum_is_valid() {
+        let h = header("file.txt", 5, b'0', "", 0o644);
+        let stored = std::str::from_utf8(&h[148..154]).unwrap();
+        let stored = u32::from_str_radix(stored, 8).unwrap();
+        assert_eq!(stored, computed_checksum(&h));
+    }
+
+    #[test]
+    fn header_records_name_size_and_magic() {
+        let h = header("dir/file.txt", 42, b'0', "", 0o644);
+        assert!(h.starts_with(b"dir/file.txt\0"));
+        assert_eq!(&h[257..263], b"ustar\0");
+        let size = u32::from_str_radix(std::str::from_utf8(&h[124..135]).unwrap(), 8).unwrap();
+        assert_eq!(size, 42);
+    }
+
+    #[test]
+    fn strip_components_drops_leading_path() {
+        assert_eq!(strip_components("a/b/c", 0), Some("a/b/c".to_string()));
+        assert_eq!(strip_components("a/b/c", 1), Some("b/c".to_string()));
+        assert_eq!(strip_components("a/b/c", 3), None);
+    }
+}
@@ -31,6 +31,34 @@ pub enum Command {
         object: String,
     },
     WriteTree {},
+    CommitTree {
+        /// Tree object to base the commit on
+        tree: String,
+        /// Parent commit(s), passed with -p
+        parents: Vec<String>,
+        /// Commit message, passed with -m
+        message: String,
+    },
+    UpdateRef {
+        /// Ref to update, e.g. refs/heads/master
+        ref_name: String,
+        /// Object the ref should point at
+        object: String,
+    },
+    Diff {
+        /// First object to compare
+        object_a: String,
+        /// Second object to compare
+        object_b: String,
+    },
+    Archive {
+        /// Tree-ish to export
+        tree_ish: String,
+        /// Path prepended to every entry name, passed with --prefix
+        prefix: String,
+        /// Leading path components to drop, passed with --strip-components
+        strip_components: usize,
+    },
 }
 
 // #[derive(Parser, Debug)]
@@ -126,6 +154,119 @@ impl Args {
                 }
             }
             "write-tree" => Ok(Command::WriteTree {}),
+            "commit-tree" => {
+                let mut tree: Option<String> = None;
+                let mut parents: Vec<String> = Vec::new();
+                let mut message: Option<String> = None;
+                while let Some(arg) = args.peek() {
+                    if arg == "-p" {
+                        args.next().unwrap();
+                        match args.next() {
+                            Some(parent) => parents.push(parent),
+                            None => {
+                                return Err(Box::new(GitError::InvalidArgs(
+                                    "missing argument for -p".to_string(),
+                                )))
+                            }
+                        }
+                    } else if arg == "-m" {
+                        args.next().unwrap();
+                        match args.next() {
+                            Some(msg) => message = Some(msg),
+                            None => {
+                                return Err(Box::new(GitError::InvalidArgs(
+                                    "missing argument for -m".to_string(),
+                                )))
+                            }
+                        }
+                    } else {
+                        // treat as positional arg <tree>
+                        tree = Some(args.next().unwrap());
+                    }
+                }
+                match (tree, message) {
+                    (Some(tree), Some(message)) => Ok(Command::CommitTree {
+                        tree,
+                        parents,
+                        message,
+                    }),
+                    (None, _) => Err(GitError::InvalidArgs(
+                        "missing positional argument <tree>".to_string(),
+                    )),
+                    (_, None) => Err(GitError::InvalidArgs("missing -m <message>".to_string())),
+                }
+            }
+            "diff" => {
+                let mut positionals: Vec<String> = Vec::new();
+                while args.peek().is_some() {
+                    positionals.push(args.next().unwrap());
+                }
+                match (positionals.first(), positionals.get(1)) {
+                    (Some(object_a), Some(object_b)) => Ok(Command::Diff {
+                        object_a: object_a.clone(),
+                        object_b: object_b.clone(),
+                    }),
+                    _ => Err(GitError::InvalidArgs(
+                        "usage: diff <objectA> <objectB>".to_string(),
+                    )),
+                }
+            }
+            "archive" => {
+                let mut tree_ish: Option<String> = None;
+                let mut prefix = String::new();
+                let mut strip_components = 0usize;
+                while let Some(arg) = args.peek() {
+                    if arg == "--prefix" {
+                        args.next().unwrap();
+                        match args.next() {
+                            Some(p) => prefix = p,
+                            None => {
+                                return Err(Box::new(GitError::InvalidArgs(
+                                    "missing argument for --prefix".to_string(),
+                                )))
+                            }
+                        }
+                    } else if arg == "--strip-components" {
+                        args.next().unwrap();
+                        match args.next() {
+                            Some(n) => strip_components = n.parse::<usize>()?,
+                            None => {
+                                return Err(Box::new(GitError::InvalidArgs(
+                                    "missing argument for --strip-components".to_string(),
+                                )))
+                            }
+                        }
+                    } else {
+                        // treat as positional arg <tree-ish>
+                        tree_ish = Some(args.next().unwrap());
+                    }
+                }
+                match tree_ish {
+                    Some(tree_ish) => Ok(Command::Archive {
+                        tree_ish,
+                        prefix,
+                        strip_components,
+                    }),
+                    None => Err(GitError::InvalidArgs(
+                        "missing positional argument <tree-ish>".to_string(),
+                    )),
+                }
+            }
+            "update-ref" => {
+                let mut positionals: Vec<String> = Vec::new();
+                while args.peek().is_some() {
+                    positionals.push(args.next().unwrap());
+                }
+                match (positionals.first(), positionals.get(1)) {
+                    (Some(ref_name), Some(object)) => Ok(Command::UpdateRef {
+                        ref_name: ref_name.clone(),
+                        object: object.clone(),
+                    }),
+                    _ => Err(GitError::InvalidArgs(
+                        "usage: update-ref <ref> <object>".to_string(),
+                    )),
+                }
+            }
             _ => Err(GitError::InvalidArgs(format!(
                 "invalid command: {}",
                 command
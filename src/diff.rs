@@ -0,0 +1,225 @@
+/// A single edit-script operation produced by the Myers diff.
+#[derive(Debug, Clone)]
+pub enum Op {
+    Equal(String),
+    Insert(String),
+    Delete(String),
+}
+
+/// Produce a unified diff (including `---`/`+++` headers) between two texts.
+/// Returns an empty string when the inputs are identical.
+pub fn unified_diff(a: &str, b: &str, label_a: &str, label_b: &str) -> String {
+    let a_lines: Vec<String> = a.lines().map(|l| l.to_string()).collect();
+    let b_lines: Vec<String> = b.lines().map(|l| l.to_string()).collect();
+    let ops = diff_lines(&a_lines, &b_lines);
+    let hunks = format_hunks(&ops, 3);
+    if hunks.is_empty() {
+        return String::new();
+    }
+    format!("--- {}\n+++ {}\n{}", label_a, label_b, hunks)
+}
+
+/// Compute the shortest edit script between two line sequences using Myers'
+/// O(ND) algorithm: advance the furthest-reaching D-path on each diagonal
+/// `k` (where `k = x - y`) in the `v` array, recording a trace per edit step,
+/// then backtrack to recover the equal/insert/delete operations.
+pub fn diff_lines(a: &[String], b: &[String]) -> Vec<Op> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max = (n + m) as usize;
+    // With no lines on either side there is nothing to edit; this also avoids
+    // indexing `v[k + 1]` below when the V array would have length 1.
+    if max == 0 {
+        return Vec::new();
+    }
+    let offset = max as isize;
+    let mut v = vec![0isize; 2 * max + 1];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'outer: for d in 0..=max as isize {
+        trace.push(v.clone());
+        let mut k = -d;
+        while k <= d {
+            // Pick the move that reaches furthest: down from diagonal `k + 1`
+            // (insert) when on the bottom edge or `k + 1` is further than
+            // `k - 1`, else right from diagonal `k - 1` (delete).
+            let mut x = if k == -d
+                || (k != d && v[(k - 1 + offset) as usize] < v[(k + 1 + offset) as usize])
+            {
+                v[(k + 1 + offset) as usize]
+            } else {
+                v[(k - 1 + offset) as usize] + 1
+            };
+            let mut y = x - k;
+            // Extend the diagonal through matching lines (a snake).
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[(k + offset) as usize] = x;
+            if x >= n && y >= m {
+                break 'outer;
+            }
+            k += 2;
+        }
+    }
+
+    backtrack(a, b, &trace, offset)
+}
+
+fn backtrack(a: &[String], b: &[String], trace: &[Vec<isize>], offset: isize) -> Vec<Op> {
+    let mut ops = Vec::new();
+    let mut x = a.len() as isize;
+    let mut y = b.len() as isize;
+
+    for d in (0..trace.len()).rev() {
+        let v = &trace[d];
+        let d = d as isize;
+        let k = x - y;
+        let prev_k = if k == -d
+            || (k != d && v[(k - 1 + offset) as usize] < v[(k + 1 + offset) as usize])
+        {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[(prev_k + offset) as usize];
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            ops.push(Op::Equal(a[(x - 1) as usize].clone()));
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if prev_k == k + 1 {
+                ops.push(Op::Insert(b[(y - 1) as usize].clone()));
+            } else {
+                ops.push(Op::Delete(a[(x - 1) as usize].clone()));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+
+    ops.reverse();
+    ops
+}
+
+/// Format an edit script into `@@ -a,b +c,d @@` hunks with `context` lines of
+/// surrounding unchanged text around each change region.
+fn format_hunks(ops: &[Op], context: usize) -> String {
+    // Annotate each op with its 1-based line numbers on each side.
+    let mut items: Vec<(char, &str, usize, usize)> = Vec::new();
+    let (mut a_no, mut b_no) = (0usize, 0usize);
+    for op in ops {
+        match op {
+            Op::Equal(t) => {
+                a_no += 1;
+                b_no += 1;
+                items.push((' ', t, a_no, b_no));
+            }
+            Op::Delete(t) => {
+                a_no += 1;
+                items.push(('-', t, a_no, 0));
+            }
+            Op::Insert(t) => {
+                b_no += 1;
+                items.push(('+', t, 0, b_no));
+            }
+        }
+    }
+
+    let changed: Vec<usize> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, it)| it.0 != ' ')
+        .map(|(i, _)| i)
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    let mut out = String::new();
+    let mut i = 0;
+    while i < changed.len() {
+        // Merge change regions that are within 2*context of one another.
+        let mut j = i;
+        while j + 1 < changed.len() && changed[j + 1] - changed[j] <= 2 * context + 1 {
+            j += 1;
+        }
+        let hstart = changed[i].saturating_sub(context);
+        let hend = (changed[j] + context).min(items.len() - 1);
+
+        let (mut a_start, mut a_count, mut b_start, mut b_count) = (0, 0, 0, 0);
+        for it in &items[hstart..=hend] {
+            if it.0 != '+' {
+                if a_start == 0 {
+                    a_start = it.2;
+                }
+                a_count += 1;
+            }
+            if it.0 != '-' {
+                if b_start == 0 {
+                    b_start = it.3;
+                }
+                b_count += 1;
+            }
+        }
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            a_start, a_count, b_start, b_count
+        ));
+        for it in &items[hstart..=hend] {
+            out.push(it.0);
+            out.push_str(it.1);
+            out.push('\n');
+        }
+
+        i = j + 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &[&str]) -> Vec<String> {
+        s.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn identical_inputs_produce_no_diff() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "a", "b"), "");
+    }
+
+    #[test]
+    fn single_line_change_is_one_delete_and_insert() {
+        let ops = diff_lines(&lines(&["a", "b", "c"]), &lines(&["a", "x", "c"]));
+        let rendered: Vec<&str> = ops
+            .iter()
+            .map(|op| match op {
+                Op::Equal(_) => "=",
+                Op::Insert(_) => "+",
+                Op::Delete(_) => "-",
+            })
+            .collect();
+        assert_eq!(rendered, vec!["=", "-", "+", "="]);
+    }
+
+    #[test]
+    fn both_empty_inputs_do_not_panic() {
+        assert!(diff_lines(&[], &[]).is_empty());
+        assert_eq!(unified_diff("", "", "a", "b"), "");
+    }
+
+    #[test]
+    fn diff_emits_headers_and_hunk() {
+        let out = unified_diff("a\nb\n", "a\nc\n", "a/f", "b/f");
+        assert!(out.starts_with("--- a/f\n+++ b/f\n"));
+        assert!(out.contains("@@"));
+        assert!(out.contains("-b"));
+        assert!(out.contains("+c"));
+    }
+}
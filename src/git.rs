@@ -15,10 +15,26 @@ use flate2::write::ZlibEncoder;
 use flate2::{read::ZlibDecoder, Compression};
 use sha1::{Digest, Sha1};
 
+use crate::Ignore;
+
 #[derive(Debug)]
 pub enum Object {
-    Blob { len: i32, content: String },
-    Tree { len: i32, entries: Vec<Entry> },
+    Blob {
+        len: i32,
+        content: Vec<u8>,
+    },
+    Tree {
+        len: i32,
+        entries: Vec<Entry>,
+    },
+    Commit {
+        len: i32,
+        tree: String,
+        parents: Vec<String>,
+        author: String,
+        committer: String,
+        message: String,
+    },
 }
 
 #[derive(Debug)]
@@ -52,12 +68,12 @@ impl Entry {
         })
     }
 
-    pub fn from_dir_entry(dir_entry: DirEntry) -> Result<Entry> {
+    pub fn from_dir_entry(dir_entry: DirEntry, ignore: &Ignore) -> Result<Entry> {
         let metadata = dir_entry.metadata()?;
         let is_dir = metadata.is_dir();
         let path = dir_entry.path();
         let object = if is_dir {
-            Object::read_from_dir(&path)?
+            Object::read_from_dir_inner(&path, ignore)?
         } else {
             Object::from_path(&path)?
         };
@@ -65,11 +81,15 @@ impl Entry {
         let type_ = match object {
             Object::Blob { len: _, content: _ } => "blob".to_string(),
             Object::Tree { len: _, entries: _ } => "tree".to_string(),
+            Object::Commit { .. } => "commit".to_string(),
         };
+        // Persist each blob and subtree as it is walked so the root tree
+        // written by `write-tree` resolves through `cat-file`/`ls-tree`.
+        object.write_to_database()?;
         let name = dir_entry
             .file_name()
             .into_string()
-            .map_err(|os| GitError::CorruptFile())?;
+            .map_err(|_| GitError::CorruptFile())?;
         let mode = Self::get_mode(&dir_entry)?;
         Ok(Entry {
             mode,
@@ -123,21 +143,33 @@ pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
 impl Object {
     pub fn read_from_sha1(object_sha: &str) -> Result<Object> {
         let (prefix, suffix) = (&object_sha[..2], &object_sha[2..]);
-        let bytes = get_object_file_as_byte_vec(prefix, suffix)?;
-        let mut rest = zlib_decompress(bytes)?.into_iter();
-        let obj_type = take_until(&mut rest, b' ');
-        let obj_type = String::from_utf8(obj_type)?;
-        let len = String::from_utf8(take_until(&mut rest, b'\0'))?
-            .parse::<i32>()
-            .unwrap();
-
-        // let (obj_type, rest) = contents.split_once(' ').ok_or(GitError::CorruptFile())?;
-        match obj_type.as_str() {
+        // Loose objects live under `.git/objects/xx/...`; when the loose file
+        // is absent fall back to scanning the packfiles.
+        let (obj_type, content) = match get_object_file_as_byte_vec(prefix, suffix) {
+            Ok(bytes) => {
+                let mut rest = zlib_decompress(bytes)?.into_iter();
+                let obj_type = String::from_utf8(take_until(&mut rest, b' '))?;
+                let _len = String::from_utf8(take_until(&mut rest, b'\0'))?
+                    .parse::<i32>()
+                    .unwrap();
+                (obj_type, rest.collect::<Vec<u8>>())
+            }
+            Err(_) => crate::pack::read_packed_object(object_sha)?,
+        };
+        Self::from_raw(&obj_type, content)
+    }
+
+    /// Build an object from its decompressed payload (the bytes after the
+    /// `{type} {len}\0` header), shared by the loose and pack read paths.
+    fn from_raw(obj_type: &str, content: Vec<u8>) -> Result<Object> {
+        let len = content.len() as i32;
+        let mut rest = content.into_iter();
+        match obj_type {
             "blob" => {
                 // Blob format: {type} {len}\0{content}
-                // Split bytes at next NUL byte and extract as the length.
+                // Keep the content as raw bytes so binary files survive a round trip.
 
-                let content = String::from_utf8(rest.collect())?;
+                let content: Vec<u8> = rest.collect();
                 Ok(Self::Blob { len, content })
             }
             "tree" => {
@@ -150,48 +182,103 @@ impl Object {
                 }
                 Ok(Self::Tree { len, entries })
             }
+            "commit" => {
+                // Commit format: header lines (`tree <sha>`, `parent <sha>`,
+                // `author ...`, `committer ...`), a blank line, then the message.
+                let text = String::from_utf8(rest.collect())?;
+                let (headers, message) = text.split_once("\n\n").unwrap_or((&text, ""));
+                let mut tree = String::new();
+                let mut parents = Vec::new();
+                let mut author = String::new();
+                let mut committer = String::new();
+                for line in headers.lines() {
+                    if let Some(rest) = line.strip_prefix("tree ") {
+                        tree = rest.to_string();
+                    } else if let Some(rest) = line.strip_prefix("parent ") {
+                        parents.push(rest.to_string());
+                    } else if let Some(rest) = line.strip_prefix("author ") {
+                        author = rest.to_string();
+                    } else if let Some(rest) = line.strip_prefix("committer ") {
+                        committer = rest.to_string();
+                    }
+                }
+                Ok(Self::Commit {
+                    len,
+                    tree,
+                    parents,
+                    author,
+                    committer,
+                    message: message.to_string(),
+                })
+            }
             _ => Err(Box::new(GitError::CorruptFile())),
         }
     }
 
+    /// Build a commit object from its parts, computing the payload length.
+    pub fn commit(
+        tree: String,
+        parents: Vec<String>,
+        author: String,
+        committer: String,
+        message: String,
+    ) -> Object {
+        let len = commit_payload(&tree, &parents, &author, &committer, &message).len() as i32;
+        Self::Commit {
+            len,
+            tree,
+            parents,
+            author,
+            committer,
+            message,
+        }
+    }
+
     pub fn from_path(path: &Path) -> Result<Object> {
-        let content = fs::read_to_string(path)?;
+        let content = fs::read(path)?;
         let len = content.len() as i32;
 
         Ok(Self::Blob { len, content })
     }
 
     pub fn read_from_dir(dir: &Path) -> Result<Object> {
-        let dir = fs::read_dir(dir)?;
-        println!("read_from_dir {:?}", &dir);
+        Self::read_from_dir_inner(dir, &Ignore::new())
+    }
+
+    fn read_from_dir_inner(dir: &Path, parent_ignore: &Ignore) -> Result<Object> {
+        // Layer this directory's `.gitignore` on top of the inherited rules.
+        let ignore = parent_ignore.load(dir)?;
+        let read = fs::read_dir(dir)?;
         let mut len = 0;
         let mut entries: Vec<Entry> = Vec::new();
 
-        for entry in dir {
+        for entry in read {
             let entry = entry?;
-            // Filter out ignored files
-            let ignored_names = ["target".to_string(), ".git".to_string()];
-            if ignored_names
-                .iter()
-                .any(|v| v.eq(&entry.file_name().into_string().unwrap()))
-            {
+            let path = entry.path();
+            let is_dir = entry.metadata()?.is_dir();
+            // The `.git` directory is never part of a tree; everything else is
+            // governed by the gitignore rules.
+            if entry.file_name().to_string_lossy() == ".git" || ignore.is_ignored(&path, is_dir) {
                 continue;
             }
-            println!("Creating entry from {:?}", entry);
-            let e = Entry::from_dir_entry(entry)?;
-            println!("Created entry {:?}", e);
+            let e = Entry::from_dir_entry(entry, &ignore)?;
             len += e.len();
             entries.push(e);
         }
+        // Git stores tree entries sorted by name, comparing subtree names as
+        // if they carried a trailing `/`; sort the same way so the tree SHA is
+        // reproducible and the entry order matches real git's.
+        entries.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
         Ok(Self::Tree { len, entries })
     }
 
     pub fn get_sha1(&self) -> Result<String> {
         match self {
             Object::Blob { len, content } => {
-                let s = format!("{} {}\0{}", "blob", len, content);
-                let bytes = Sha1::digest(s.as_bytes());
-                Ok(format!("{:x}", bytes))
+                let mut bytes = format!("{} {}\0", "blob", len).into_bytes();
+                bytes.extend_from_slice(content);
+                let hash = Sha1::digest(&bytes);
+                Ok(format!("{:x}", hash))
             }
             Object::Tree { len, entries } => {
                 // Format: {type} {len}\0[{mode} {file/dir name}\0{SHA1 hash}]*
@@ -206,7 +293,21 @@ impl Object {
                     bytes.push(e.to_bytes())
                 }
                 let hash = Sha1::digest(&bytes.concat());
-                println!("creating hash {:?} => {:?}", bytes.concat(), hash);
+                Ok(format!("{:x}", hash))
+            }
+            Object::Commit {
+                len,
+                tree,
+                parents,
+                author,
+                committer,
+                message,
+            } => {
+                let mut bytes = format!("commit {}\0", len).into_bytes();
+                bytes.extend_from_slice(&commit_payload(
+                    tree, parents, author, committer, message,
+                ));
+                let hash = Sha1::digest(&bytes);
                 Ok(format!("{:x}", hash))
             }
         }
@@ -219,15 +320,78 @@ impl Object {
         std::fs::create_dir_all(path.parent().unwrap())?;
         let mut file = File::create(path)?;
         let data = match self {
-            Object::Blob { len, content } => format!("blob {}\0{}", len, content),
-            Object::Tree { len: _, entries: _ } => todo!(),
+            Object::Blob { len, content } => {
+                let mut data = format!("blob {}\0", len).into_bytes();
+                data.extend_from_slice(content);
+                data
+            }
+            Object::Tree { len, entries } => {
+                // Format: {type} {len}\0[{mode} {file/dir name}\0{SHA1 hash}]*
+                // Mirror the concatenation used by `get_sha1`.
+                let mut data = format!("tree {}\0", len).into_bytes();
+                for e in entries {
+                    data.extend_from_slice(&e.to_bytes());
+                }
+                data
+            }
+            Object::Commit {
+                len,
+                tree,
+                parents,
+                author,
+                committer,
+                message,
+            } => {
+                let mut data = format!("commit {}\0", len).into_bytes();
+                data.extend_from_slice(&commit_payload(
+                    tree, parents, author, committer, message,
+                ));
+                data
+            }
         };
         let data_bin = zlib_compress(data)?;
-        file.write(&data_bin)?;
+        file.write_all(&data_bin)?;
         Ok(())
     }
 }
 
+/// The key git uses to order tree entries: a subtree sorts as if its name had
+/// a trailing `/`, so e.g. `foo.txt` precedes the directory `foo`.
+fn sort_key(entry: &Entry) -> Vec<u8> {
+    let mut key = entry.name.clone().into_bytes();
+    if entry.type_ == "tree" {
+        key.push(b'/');
+    }
+    key
+}
+
+/// Serialize the body of a commit object (everything after the `commit {len}\0` header).
+fn commit_payload(
+    tree: &str,
+    parents: &[String],
+    author: &str,
+    committer: &str,
+    message: &str,
+) -> Vec<u8> {
+    let mut payload = format!("tree {}\n", tree);
+    for parent in parents {
+        payload.push_str(&format!("parent {}\n", parent));
+    }
+    payload.push_str(&format!("author {}\n", author));
+    payload.push_str(&format!("committer {}\n", committer));
+    payload.push('\n');
+    payload.push_str(message);
+    payload.into_bytes()
+}
+
+/// Point a ref (e.g. `refs/heads/master`) at a commit hash.
+pub fn update_ref(ref_name: &str, sha1: &str) -> Result<()> {
+    let path = Path::new(".git").join(ref_name);
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(path, format!("{}\n", sha1))?;
+    Ok(())
+}
+
 /// Takes in an itterable of bytes and returns a Vec of bytes the the left of the target or the whole Iterable if target not found.
 fn take_until<'a>(bytes: &mut IntoIter<u8>, target: u8) -> Vec<u8> {
     let type_buf: Vec<u8> = bytes.by_ref().take_while(|b| *b != target).collect();
@@ -239,7 +403,7 @@ fn get_object_file_as_byte_vec(prefix: &str, suffix: &str) -> Result<Vec<u8>> {
     let mut f = File::open(&path)?;
     let metadata = fs::metadata(&path).expect("unable to read metadata");
     let mut buffer = vec![0; metadata.len() as usize];
-    f.read(&mut buffer).expect("buffer overflow");
+    f.read_exact(&mut buffer).expect("buffer overflow");
     Ok(buffer)
 }
 
@@ -250,9 +414,9 @@ fn zlib_decompress(bytes: Vec<u8>) -> Result<Vec<u8>> {
     Ok(buf)
 }
 
-fn zlib_compress(s: String) -> Result<Vec<u8>> {
+fn zlib_compress(bytes: Vec<u8>) -> Result<Vec<u8>> {
     let mut e = ZlibEncoder::new(Vec::new(), Compression::default());
-    e.write(s.as_bytes())?;
+    e.write_all(&bytes)?;
     let compressed = e.finish()?;
     Ok(compressed)
 }
@@ -266,7 +430,7 @@ pub fn decode_hex(s: &str) -> std::result::Result<Vec<u8>, ParseIntError> {
         .collect()
 }
 
-fn encode_hex(bytes: &[u8]) -> String {
+pub fn encode_hex(bytes: &[u8]) -> String {
     let mut s = String::with_capacity(bytes.len() * 2);
     for &b in bytes {
         fmt::write(&mut s, format_args!("{:02x}", b)).unwrap();
@@ -0,0 +1,164 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::Result;
+
+/// A stack of compiled `.gitignore` rules. A fresh `Ignore` is created for the
+/// repository root and each directory layers its own `.gitignore` on top of
+/// its parent's rules, with later rules overriding earlier ones.
+#[derive(Debug, Clone, Default)]
+pub struct Ignore {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    /// Directory the rule's `.gitignore` lives in; patterns match relative to it.
+    base: PathBuf,
+    /// The glob, with any leading `!`, leading `/` and trailing `/` stripped.
+    pattern: String,
+    /// `!`-prefixed rules un-ignore a previously ignored path.
+    negated: bool,
+    /// Trailing-`/` rules only match directories.
+    dir_only: bool,
+    /// Rules containing a `/` are anchored to `base`; others match at any depth.
+    anchored: bool,
+}
+
+impl Ignore {
+    pub fn new() -> Ignore {
+        Ignore::default()
+    }
+
+    /// Return a new `Ignore` with `dir`'s `.gitignore` (if any) layered on top.
+    pub fn load(&self, dir: &Path) -> Result<Ignore> {
+        let mut rules = self.rules.clone();
+        let gitignore = dir.join(".gitignore");
+        if gitignore.exists() {
+            for line in fs::read_to_string(&gitignore)?.lines() {
+                if let Some(rule) = Rule::parse(line, dir) {
+                    rules.push(rule);
+                }
+            }
+        }
+        Ok(Ignore { rules })
+    }
+
+    /// Whether `path` (a directory when `is_dir`) should be ignored. The last
+    /// matching rule wins, so a later `!pattern` can re-include a path.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.matches(path, is_dir) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+impl Rule {
+    fn parse(line: &str, base: &Path) -> Option<Rule> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let mut pat = line;
+        let negated = pat.starts_with('!');
+        if negated {
+            pat = &pat[1..];
+        }
+        let dir_only = pat.ends_with('/');
+        if dir_only {
+            pat = &pat[..pat.len() - 1];
+        }
+        let anchored = pat.starts_with('/') || pat.contains('/');
+        let pat = pat.trim_start_matches('/');
+        Some(Rule {
+            base: base.to_path_buf(),
+            pattern: pat.to_string(),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let rel = match path.strip_prefix(&self.base) {
+            Ok(rel) => rel,
+            Err(_) => return false,
+        };
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        if self.anchored {
+            glob_match(&self.pattern, &rel)
+        } else {
+            // Unanchored rules match the file name at any depth.
+            let name = rel.rsplit('/').next().unwrap_or(&rel);
+            glob_match(&self.pattern, name)
+        }
+    }
+}
+
+/// Match a gitignore glob against `text`. `*` matches any run of non-`/`
+/// characters, `**` matches across `/`, and `?` matches a single non-`/` char.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    glob(&p, &t)
+}
+
+fn glob(p: &[char], t: &[char]) -> bool {
+    if p.is_empty() {
+        return t.is_empty();
+    }
+    match p[0] {
+        '*' if p.get(1) == Some(&'*') => {
+            // `**` matches any sequence, including `/`.
+            let mut rest = &p[2..];
+            if rest.first() == Some(&'/') {
+                rest = &rest[1..];
+            }
+            (0..=t.len()).any(|i| glob(rest, &t[i..]))
+        }
+        '*' => {
+            // `*` matches zero or more characters up to the next `/`.
+            for i in 0..=t.len() {
+                if glob(&p[1..], &t[i..]) {
+                    return true;
+                }
+                if t.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        '?' => matches!(t.first(), Some(c) if *c != '/') && glob(&p[1..], &t[1..]),
+        c => t.first() == Some(&c) && glob(&p[1..], &t[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_does_not_cross_slash() {
+        assert!(glob_match("*.rs", "main.rs"));
+        assert!(!glob_match("*.rs", "src/main.rs"));
+    }
+
+    #[test]
+    fn double_star_crosses_slash() {
+        assert!(glob_match("**/main.rs", "src/bin/main.rs"));
+        assert!(glob_match("**/main.rs", "main.rs"));
+    }
+
+    #[test]
+    fn question_mark_matches_single_char() {
+        assert!(glob_match("file?.txt", "file1.txt"));
+        assert!(!glob_match("file?.txt", "file.txt"));
+    }
+}
@@ -1,5 +1,6 @@
 // use clap::{Parser, Subcommand};
 use std::{
+    collections::BTreeMap,
     env,
     fs::{self},
     path::Path,
@@ -9,6 +10,11 @@ mod git;
 pub use git::*;
 mod args;
 pub use args::*;
+mod pack;
+mod ignore;
+pub use ignore::*;
+mod diff;
+mod archive;
 
 fn main() -> crate::Result<()> {
     let args = Args::parse();
@@ -36,6 +42,24 @@ fn main() -> crate::Result<()> {
         }
         Command::LsTree { name_only, object } => ls_tree(Command::LsTree { name_only, object }),
         Command::WriteTree {} => write_tree(Command::WriteTree {}),
+        Command::CommitTree {
+            tree,
+            parents,
+            message,
+        } => commit_tree(Command::CommitTree {
+            tree,
+            parents,
+            message,
+        }),
+        Command::UpdateRef { ref_name, object } => {
+            update_ref(&ref_name, &object).map(|_| println!("{}", object))
+        }
+        Command::Diff { object_a, object_b } => diff_objects(Command::Diff { object_a, object_b }),
+        Command::Archive {
+            tree_ish,
+            prefix,
+            strip_components,
+        } => archive::archive(&tree_ish, &prefix, strip_components),
     };
     if let Err(why) = result {
         println!("fatal: {}", &why);
@@ -71,7 +95,7 @@ fn cat_file(command: Command) -> Result<()> {
                     println!("{}", len);
                 }
                 if pretty_print {
-                    print!("{}", content);
+                    print!("{}", String::from_utf8_lossy(&content));
                 }
             }
             Object::Tree { len, entries } => {
@@ -90,6 +114,31 @@ fn cat_file(command: Command) -> Result<()> {
                     }
                 }
             }
+            Object::Commit {
+                len,
+                tree,
+                parents,
+                author,
+                committer,
+                message,
+            } => {
+                if print_type {
+                    println!("commit");
+                }
+                if print_size {
+                    println!("{}", len);
+                }
+                if pretty_print {
+                    println!("tree {}", tree);
+                    for parent in parents {
+                        println!("parent {}", parent);
+                    }
+                    println!("author {}", author);
+                    println!("committer {}", committer);
+                    println!();
+                    print!("{}", message);
+                }
+            }
         }
 
         Ok(())
@@ -137,6 +186,97 @@ fn ls_tree(command: Command) -> Result<()> {
     }
 }
 
+fn commit_tree(command: Command) -> Result<()> {
+    if let Command::CommitTree {
+        tree,
+        parents,
+        message,
+    } = command
+    {
+        // A real git reads these from config/`GIT_*` env; fall back to a
+        // fixed identity and timestamp when they are unset.
+        let author = env::var("GIT_AUTHOR").unwrap_or_else(|_| {
+            "rs-git <rs-git@localhost> 0 +0000".to_string()
+        });
+        let committer = env::var("GIT_COMMITTER").unwrap_or_else(|_| author.clone());
+        let commit = Object::commit(tree, parents, author, committer, message);
+        commit.write_to_database()?;
+        println!("{}", commit.get_sha1()?);
+        Ok(())
+    } else {
+        panic!("Unreachable");
+    }
+}
+
+fn diff_objects(command: Command) -> Result<()> {
+    if let Command::Diff { object_a, object_b } = command {
+        let a = Object::read_from_sha1(&object_a)?;
+        let b = Object::read_from_sha1(&object_b)?;
+        match (a, b) {
+            (Object::Blob { content: ca, .. }, Object::Blob { content: cb, .. }) => {
+                print!(
+                    "{}",
+                    diff::unified_diff(
+                        &String::from_utf8_lossy(&ca),
+                        &String::from_utf8_lossy(&cb),
+                        &format!("a/{}", object_a),
+                        &format!("b/{}", object_b),
+                    )
+                );
+            }
+            (Object::Tree { .. }, Object::Tree { .. }) => {
+                let mut a_files = BTreeMap::new();
+                collect_blobs(&object_a, "", &mut a_files)?;
+                let mut b_files = BTreeMap::new();
+                collect_blobs(&object_b, "", &mut b_files)?;
+
+                let mut paths: Vec<&String> = a_files.keys().chain(b_files.keys()).collect();
+                paths.sort();
+                paths.dedup();
+                for path in paths {
+                    let empty = String::new();
+                    let left = a_files.get(path).unwrap_or(&empty);
+                    let right = b_files.get(path).unwrap_or(&empty);
+                    let diff = diff::unified_diff(
+                        left,
+                        right,
+                        &format!("a/{}", path),
+                        &format!("b/{}", path),
+                    );
+                    print!("{}", diff);
+                }
+            }
+            _ => {
+                return Err(Box::new(GitError::InvalidArgs(
+                    "cannot diff a blob against a tree".to_string(),
+                )))
+            }
+        }
+        Ok(())
+    } else {
+        panic!("Unreachable");
+    }
+}
+
+/// Recursively collect a tree's blob contents keyed by path (decoded lossily).
+fn collect_blobs(sha: &str, prefix: &str, files: &mut BTreeMap<String, String>) -> Result<()> {
+    if let Object::Tree { entries, .. } = Object::read_from_sha1(sha)? {
+        for entry in entries {
+            let path = if prefix.is_empty() {
+                entry.name.clone()
+            } else {
+                format!("{}/{}", prefix, entry.name)
+            };
+            if entry.type_ == "tree" {
+                collect_blobs(&entry.sha1, &path, files)?;
+            } else if let Object::Blob { content, .. } = Object::read_from_sha1(&entry.sha1)? {
+                files.insert(path, String::from_utf8_lossy(&content).to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
 fn write_tree(command: Command) -> Result<()> {
     if let Command::WriteTree {} = command {
         let dir = Object::read_from_dir(&env::current_dir()?)?;